@@ -1,9 +1,14 @@
-//! 
-//! Functions for simplifying the process of serializing types to JSON files.
+//!
+//! Functions for simplifying the process of serializing types to files.
 //!
 //! Supports both rustc-serialize (by default) and serde via the `--features="serde_serialization"
 //! --no-default-features` flags.
 //!
+//! When using serde, JSON is supported out of the box and `save`/`load` always target it. Enable
+//! the `yaml_serialization`, `toml_serialization`, `ron_serialization` and/or `json5_serialization`
+//! features to also pull in those backends, and use `save_as`/`load_as` along with the `Format`
+//! enum to target them explicitly.
+//!
 
 
 #[cfg(feature="rustc-serialize")]
@@ -12,13 +17,26 @@ pub use rustc_serialize::{Error, load, save};
 
 #[cfg(feature="serde_serialization")]
 #[cfg(not(feature="rustc_serialization"))]
-pub use serde::{Error, load, save};
+pub use serde::{
+    Error, Format,
+    load, save, load_as, save_as,
+    load_ndjson, load_ndjson_iter, save_ndjson,
+};
 
 
 #[cfg(feature="serde_serialization")]
 mod serde {
     extern crate serde;
     extern crate serde_json;
+    extern crate serde_path_to_error;
+    #[cfg(feature="yaml_serialization")]
+    extern crate serde_yaml;
+    #[cfg(feature="toml_serialization")]
+    extern crate toml;
+    #[cfg(feature="ron_serialization")]
+    extern crate ron;
+    #[cfg(feature="json5_serialization")]
+    extern crate json5;
 
     use std;
 
@@ -32,6 +50,56 @@ mod serde {
         /// This type represents all possible errors that can occur when serializing or
         /// deserializing a value into JSON (returned by the serde_json crate).
         Json(serde_json::error::Error),
+        /// Returned when deserializing JSON fails, naming the path to the offending field (e.g.
+        /// `config.servers[2].port`) alongside the underlying `serde_json` error.
+        DeserializePath {
+            /// The path to the field that failed to deserialize.
+            path: String,
+            /// The underlying error describing why it failed.
+            source: serde_json::error::Error,
+        },
+        /// Returned when serializing JSON fails, naming the path to the offending field.
+        SerializePath {
+            /// The path to the field that failed to serialize.
+            path: String,
+            /// The underlying error describing why it failed.
+            source: serde_json::error::Error,
+        },
+        /// An error returned by the serde_yaml crate while serializing or deserializing YAML.
+        #[cfg(feature="yaml_serialization")]
+        Yaml(serde_yaml::Error),
+        /// An error returned by the toml crate while deserializing TOML.
+        #[cfg(feature="toml_serialization")]
+        TomlDe(toml::de::Error),
+        /// An error returned by the toml crate while serializing TOML.
+        #[cfg(feature="toml_serialization")]
+        TomlSer(toml::ser::Error),
+        /// An error returned by the ron crate while serializing or deserializing RON (ron uses a
+        /// single `Error` type for both directions).
+        #[cfg(feature="ron_serialization")]
+        Ron(ron::Error),
+        /// An error returned by the json5 crate while serializing or deserializing JSON5.
+        #[cfg(feature="json5_serialization")]
+        Json5(json5::Error),
+        /// Returned by `load`/`save`/`load_as`/`save_as` and the NDJSON functions to attach the
+        /// path of the file that caused `source`, along with a truncated excerpt of the input if
+        /// `load`/`load_as` failed to parse it.
+        File {
+            /// The file that was being read or written when `source` occurred.
+            path: std::path::PathBuf,
+            /// A truncated excerpt (~200 chars) of the input that failed to parse, if any.
+            snippet: Option<String>,
+            /// The underlying error.
+            source: Box<Error>,
+        },
+        /// Returned by `load_ndjson`/`load_ndjson_iter` to attach the 1-based line number of the
+        /// NDJSON record that failed to parse.
+        Line {
+            /// The 1-based line number of the record that failed.
+            line: usize,
+            /// The underlying error.
+            source: Box<Error>,
+        },
     }
 
     impl std::fmt::Display for Error {
@@ -40,6 +108,28 @@ mod serde {
                 Error::IO(ref err) => std::fmt::Display::fmt(err, f),
                 Error::Utf8(ref err) => std::fmt::Display::fmt(err, f),
                 Error::Json(ref err) => std::fmt::Display::fmt(err, f),
+                Error::DeserializePath { ref path, ref source } =>
+                    write!(f, "failed to deserialize `{}`: {}", path, source),
+                Error::SerializePath { ref path, ref source } =>
+                    write!(f, "failed to serialize `{}`: {}", path, source),
+                #[cfg(feature="yaml_serialization")]
+                Error::Yaml(ref err) => std::fmt::Display::fmt(err, f),
+                #[cfg(feature="toml_serialization")]
+                Error::TomlDe(ref err) => std::fmt::Display::fmt(err, f),
+                #[cfg(feature="toml_serialization")]
+                Error::TomlSer(ref err) => std::fmt::Display::fmt(err, f),
+                #[cfg(feature="ron_serialization")]
+                Error::Ron(ref err) => std::fmt::Display::fmt(err, f),
+                #[cfg(feature="json5_serialization")]
+                Error::Json5(ref err) => std::fmt::Display::fmt(err, f),
+                Error::File { ref path, ref snippet, ref source } => {
+                    try!(write!(f, "failed to process \"{}\": {}", path.display(), source));
+                    if let Some(ref snippet) = *snippet {
+                        try!(write!(f, " (input: \"{}\")", snippet));
+                    }
+                    Ok(())
+                }
+                Error::Line { line, ref source } => write!(f, "line {}: {}", line, source),
             }
         }
     }
@@ -50,6 +140,20 @@ mod serde {
                 Error::IO(ref err) => std::error::Error::description(err),
                 Error::Utf8(ref err) => std::error::Error::description(err),
                 Error::Json(ref err) => std::error::Error::description(err),
+                Error::DeserializePath { ref source, .. } => std::error::Error::description(source),
+                Error::SerializePath { ref source, .. } => std::error::Error::description(source),
+                #[cfg(feature="yaml_serialization")]
+                Error::Yaml(ref err) => std::error::Error::description(err),
+                #[cfg(feature="toml_serialization")]
+                Error::TomlDe(ref err) => std::error::Error::description(err),
+                #[cfg(feature="toml_serialization")]
+                Error::TomlSer(ref err) => std::error::Error::description(err),
+                #[cfg(feature="ron_serialization")]
+                Error::Ron(ref err) => std::error::Error::description(err),
+                #[cfg(feature="json5_serialization")]
+                Error::Json5(ref err) => std::error::Error::description(err),
+                Error::File { ref source, .. } => std::error::Error::description(source),
+                Error::Line { ref source, .. } => std::error::Error::description(source),
             }
         }
     }
@@ -72,43 +176,777 @@ mod serde {
         }
     }
 
+    #[cfg(feature="yaml_serialization")]
+    impl From<serde_yaml::Error> for Error {
+        fn from(err: serde_yaml::Error) -> Self {
+            Error::Yaml(err)
+        }
+    }
+
+    #[cfg(feature="toml_serialization")]
+    impl From<toml::de::Error> for Error {
+        fn from(err: toml::de::Error) -> Self {
+            Error::TomlDe(err)
+        }
+    }
+
+    #[cfg(feature="toml_serialization")]
+    impl From<toml::ser::Error> for Error {
+        fn from(err: toml::ser::Error) -> Self {
+            Error::TomlSer(err)
+        }
+    }
+
+    #[cfg(feature="ron_serialization")]
+    impl From<ron::Error> for Error {
+        fn from(err: ron::Error) -> Self {
+            Error::Ron(err)
+        }
+    }
+
+    #[cfg(feature="json5_serialization")]
+    impl From<json5::Error> for Error {
+        fn from(err: json5::Error) -> Self {
+            Error::Json5(err)
+        }
+    }
+
+    /// The set of file formats that `json_io` knows how to save and load.
+    ///
+    /// `Json` is always available. The other variants are gated behind their own cargo feature
+    /// (`yaml_serialization`, `toml_serialization`, `ron_serialization`, `json5_serialization`) so
+    /// that consumers only pull in the backends they actually need.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Format {
+        /// JavaScript Object Notation.
+        Json,
+        /// YAML Ain't Markup Language.
+        #[cfg(feature="yaml_serialization")]
+        Yaml,
+        /// Tom's Obvious, Minimal Language.
+        #[cfg(feature="toml_serialization")]
+        Toml,
+        /// Rusty Object Notation.
+        #[cfg(feature="ron_serialization")]
+        Ron,
+        /// JSON5, a superset of JSON that reads more like hand-written config.
+        #[cfg(feature="json5_serialization")]
+        Json5,
+    }
+
+    impl Format {
+        /// Infer a `Format` from the given path's extension.
+        ///
+        /// Falls back to `Json` if the extension is missing or isn't recognised (or if the
+        /// feature required to recognise it isn't enabled).
+        pub fn from_path<P>(path: P) -> Self
+            where P: AsRef<std::path::Path>,
+        {
+            match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+                #[cfg(feature="yaml_serialization")]
+                Some("yaml") | Some("yml") => Format::Yaml,
+                #[cfg(feature="toml_serialization")]
+                Some("toml") => Format::Toml,
+                #[cfg(feature="ron_serialization")]
+                Some("ron") => Format::Ron,
+                #[cfg(feature="json5_serialization")]
+                Some("json5") => Format::Json5,
+                _ => Format::Json,
+            }
+        }
+
+        /// Serialize `value` to a `String` using this format.
+        pub fn to_string<T>(&self, value: &T) -> Result<String, Error>
+            where T: serde::Serialize,
+        {
+            match *self {
+                Format::Json => json_to_string(value),
+                #[cfg(feature="yaml_serialization")]
+                Format::Yaml => Ok(try!(serde_yaml::to_string(value))),
+                #[cfg(feature="toml_serialization")]
+                Format::Toml => Ok(try!(toml::to_string(value))),
+                #[cfg(feature="ron_serialization")]
+                Format::Ron => Ok(try!(ron::ser::to_string(value))),
+                #[cfg(feature="json5_serialization")]
+                Format::Json5 => Ok(try!(json5::to_string(value))),
+            }
+        }
+
+        /// Deserialize a value of type `T` from `s` using this format.
+        pub fn from_str<T>(&self, s: &str) -> Result<T, Error>
+            where T: serde::de::DeserializeOwned,
+        {
+            match *self {
+                Format::Json => json_from_str(s),
+                #[cfg(feature="yaml_serialization")]
+                Format::Yaml => Ok(try!(serde_yaml::from_str(s))),
+                #[cfg(feature="toml_serialization")]
+                Format::Toml => Ok(try!(toml::from_str(s))),
+                #[cfg(feature="ron_serialization")]
+                Format::Ron => Ok(try!(ron::de::from_str(s))),
+                #[cfg(feature="json5_serialization")]
+                Format::Json5 => Ok(try!(json5::from_str(s))),
+            }
+        }
+    }
+
+    /// Deserialize a value of type `T` from a JSON string, reporting the exact path to the
+    /// offending field (e.g. `config.servers[2].port`) if deserialization fails.
+    fn json_from_str<T>(s: &str) -> Result<T, Error>
+        where T: serde::de::DeserializeOwned,
+    {
+        let mut de = serde_json::Deserializer::from_str(s);
+        serde_path_to_error::deserialize(&mut de).map_err(|err| {
+            let path = err.path().to_string();
+            Error::DeserializePath { path, source: err.into_inner() }
+        })
+    }
+
+    /// Serialize `value` to a JSON string, reporting the exact path to the offending field if
+    /// serialization fails.
+    fn json_to_string<T>(value: &T) -> Result<String, Error>
+        where T: serde::Serialize,
+    {
+        let mut bytes = Vec::new();
+        {
+            let mut ser = serde_json::Serializer::new(&mut bytes);
+            try!(serde_path_to_error::serialize(value, &mut ser).map_err(|err| {
+                let path = err.path().to_string();
+                Error::SerializePath { path, source: err.into_inner() }
+            }));
+        }
+        Ok(String::from_utf8(bytes).expect("serde_json only ever writes valid utf8"))
+    }
+
+    /// The number of characters of a bad input to keep when attaching it to an `Error::File`.
+    const SNIPPET_MAX_CHARS: usize = 200;
+
+    /// Truncate `s` to `SNIPPET_MAX_CHARS` characters (on a char boundary), appending `...` if
+    /// anything was cut off.
+    fn snippet(s: &str) -> String {
+        match s.char_indices().nth(SNIPPET_MAX_CHARS) {
+            Some((end, _)) => format!("{}...", &s[..end]),
+            None => s.to_string(),
+        }
+    }
+
+    /// Wrap `result` in an `Error::File` naming `path`. If `result` is an `Err` and `input` was
+    /// given, a truncated excerpt of it is attached too; `input` is only ever inspected on the
+    /// error path, so a successful `result` pays no snippet-truncation cost.
+    fn with_path<T>(path: &std::path::Path, input: Option<&str>, result: Result<T, Error>)
+        -> Result<T, Error>
+    {
+        result.map_err(|err| Error::File {
+            path: path.to_path_buf(),
+            snippet: input.map(snippet),
+            source: Box::new(err),
+        })
+    }
+
+    /// Read the file at `path` to a `String`, falling back to the `.json` extension if it isn't
+    /// found at the given path and `json_fallback` is `true`. Returns the path that was actually opened (which differs from
+    /// `path` when the fallback kicked in) alongside its contents.
+    ///
+    /// Every error returned is already an `Error::File` naming whichever path was actually
+    /// opened when it occurred (`path` or the fallback), so callers should propagate it as-is
+    /// rather than wrapping it again.
+    fn read_to_string(path: &std::path::Path, json_fallback: bool)
+        -> Result<(std::path::PathBuf, String), Error>
+    {
+        let (resolved, mut file) = match std::fs::File::open(path) {
+            Ok(file) => (path.to_path_buf(), file),
+            Err(err) => match err.kind() {
+                std::io::ErrorKind::NotFound if json_fallback => {
+                    let fallback = path.with_extension("json");
+                    match std::fs::File::open(&fallback) {
+                        Ok(file) => (fallback, file),
+                        Err(err) => return with_path(&fallback, None, Err(err.into())),
+                    }
+                }
+                _ => return with_path(path, None, Err(err.into())),
+            },
+        };
+        let mut contents = Vec::new();
+        if let Err(err) = std::io::Read::read_to_end(&mut file, &mut contents) {
+            return with_path(&resolved, None, Err(err.into()));
+        }
+        match String::from_utf8(contents) {
+            Ok(contents) => Ok((resolved, contents)),
+            Err(err) => with_path(&resolved, None, Err(err.utf8_error().into())),
+        }
+    }
+
+    /// Monotonic counter mixed into temporary file names, alongside the process id, so that two
+    /// concurrent writers targeting the same destination (e.g. two `save` calls racing on the
+    /// same config path) never collide on the same temp file.
+    static TMP_FILE_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    /// Write to `path` atomically by handing a sibling temporary file (in the same directory, so
+    /// the closing rename is on the same filesystem) to `write`, then flushing, `sync_all`-ing and
+    /// renaming it over `path`. A reader can therefore never observe a half-written file, even if
+    /// the process dies mid-write or `write` streams its output one piece at a time.
+    ///
+    /// The temporary file's name is unique per call (pid + a monotonic counter), and is removed
+    /// if `write` or the subsequent flush/sync fails, so a failed write never leaves stray or
+    /// corrupted `.tmp` files behind.
+    fn atomic_write_with<F>(path: &std::path::Path, write: F) -> Result<(), Error>
+        where F: FnOnce(&mut std::fs::File) -> Result<(), Error>,
+    {
+        let unique = TMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut tmp_name = path.file_name()
+            .unwrap_or_else(|| std::ffi::OsStr::new("json_io"))
+            .to_os_string();
+        tmp_name.push(format!(".{}.{}.tmp", std::process::id(), unique));
+        let tmp_path = path.with_file_name(tmp_name);
+        let result: Result<(), Error> = (|| {
+            let mut tmp_file = try!(std::fs::File::create(&tmp_path));
+            try!(write(&mut tmp_file));
+            try!(std::io::Write::flush(&mut tmp_file));
+            try!(tmp_file.sync_all());
+            Ok(())
+        })();
+        if let Err(err) = result {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+        if let Err(err) = std::fs::rename(&tmp_path, path) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// Write `contents` to `path` atomically; see `atomic_write_with` for the mechanism.
+    fn atomic_write(path: &std::path::Path, contents: &[u8]) -> Result<(), Error> {
+        atomic_write_with(path, |file| {
+            try!(std::io::Write::write_all(file, contents));
+            Ok(())
+        })
+    }
+
     /// Construct a Deserializable type from a JSON file at the given path.
     ///
     /// json_io will first try and open the file with the path exactly as given.
     ///
     /// If the file isn't found, it will set the extension to .json and try again.
+    ///
+    /// If this fails, the returned `Error::File` names whichever path was actually opened (`path`
+    /// or its `.json` fallback) and, if the file was read but could not be parsed, carries a
+    /// truncated excerpt of its contents.
     pub fn load<P, T>(path: P) -> Result<T, Error>
         where P: AsRef<std::path::Path>,
-              T: serde::Deserialize,
+              T: serde::de::DeserializeOwned,
     {
         let path = path.as_ref();
-        let mut file = match std::fs::File::open(&path) {
-            Ok(file) => file,
-            Err(err) => match err.kind() {
-                std::io::ErrorKind::NotFound =>
-                    try!(std::fs::File::open(&path.with_extension("json"))),
-                _ => return Err(err.into()),
-            },
-        };
-        let mut contents = Vec::new();
-        try!(std::io::Read::read_to_end(&mut file, &mut contents));
-        let json_str = try!(std::str::from_utf8(&contents[..]));
-        let t: T = try!(serde_json::from_str(&json_str));
-        Ok(t)
+        let (resolved, contents) = try!(read_to_string(path, true));
+        with_path(&resolved, Some(&contents), json_from_str(&contents))
     }
 
     /// Save an Encodable type to a JSON file at the given path.
     ///
     /// The file will be saved with the ".json" extension whether or not it was given with the Path.
+    /// The write is atomic: `t` is serialized up front, written to a sibling temporary file, then
+    /// renamed over the destination, so a reader can never observe a half-written file.
     pub fn save<P, T>(path: P, t: &T) -> Result<(), Error>
         where P: AsRef<std::path::Path>,
               T: serde::Serialize,
     {
         let path = path.as_ref();
-        let json_string = try!(serde_json::to_string(&t));
-        let mut file = try!(std::fs::File::create(&path.with_extension("json")));
-        try!(std::io::Write::write_all(&mut file, json_string.as_bytes()));
-        Ok(())
+        let dest = path.with_extension("json");
+        let json_string = try!(with_path(&dest, None, json_to_string(t)));
+        let result = atomic_write(&dest, json_string.as_bytes());
+        with_path(&dest, None, result)
+    }
+
+    /// Construct a Deserializable type from a file at the given path, using `format` rather than
+    /// always assuming JSON.
+    ///
+    /// Unlike `load`, the path is used exactly as given; pair this with `Format::from_path` if
+    /// you'd like the format inferred from the file's extension. See `load` for the shape of the
+    /// error returned on failure.
+    pub fn load_as<P, T>(path: P, format: Format) -> Result<T, Error>
+        where P: AsRef<std::path::Path>,
+              T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let (resolved, contents) = try!(read_to_string(path, false));
+        with_path(&resolved, Some(&contents), format.from_str(&contents))
+    }
+
+    /// Save an Encodable type to a file at the given path, using `format` rather than always
+    /// writing JSON.
+    ///
+    /// Unlike `save`, the path's extension is left untouched; pair this with `Format::from_path`
+    /// if you'd like the format inferred from the file's extension. The write is atomic and
+    /// errors are reported the same way as `save`.
+    pub fn save_as<P, T>(path: P, t: &T, format: Format) -> Result<(), Error>
+        where P: AsRef<std::path::Path>,
+              T: serde::Serialize,
+    {
+        let path = path.as_ref();
+        let s = try!(with_path(path, None, format.to_string(t)));
+        let result = atomic_write(path, s.as_bytes());
+        with_path(path, None, result)
+    }
+
+    /// An iterator over the records of a newline-delimited JSON (NDJSON) file, returned by
+    /// `load_ndjson_iter`.
+    ///
+    /// Each line is deserialized independently, so a single malformed record surfaces as an
+    /// `Error::File` (naming the file) wrapping an `Error::Line` (naming the line), without
+    /// preventing the rest of the file from being read.
+    struct NdjsonLines<T> {
+        path: std::path::PathBuf,
+        lines: std::io::Lines<std::io::BufReader<std::fs::File>>,
+        line: usize,
+        marker: std::marker::PhantomData<T>,
+    }
+
+    impl<T> Iterator for NdjsonLines<T>
+        where T: serde::de::DeserializeOwned,
+    {
+        type Item = Result<T, Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let next = match self.lines.next() {
+                    None => return None,
+                    Some(next) => next,
+                };
+                self.line += 1;
+                let line = match next {
+                    Err(err) => return Some(with_path(&self.path, None, Err(err.into()))),
+                    Ok(line) => line,
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let line_no = self.line;
+                let result = json_from_str(&line).map_err(|err| Error::Line {
+                    line: line_no,
+                    source: Box::new(err),
+                });
+                return Some(with_path(&self.path, None, result));
+            }
+        }
+    }
+
+    /// Open the NDJSON file at `path`, falling back to the `.ndjson` extension if it isn't found,
+    /// mirroring the `.json` fallback used by `load`. Returns the path that was actually opened
+    /// (which differs from `path` when the fallback kicked in) alongside the open file.
+    ///
+    /// Every error returned is already an `Error::File` naming whichever path was actually
+    /// opened when it occurred (`path` or the fallback), so callers should propagate it as-is
+    /// rather than wrapping it again.
+    fn open_ndjson(path: &std::path::Path) -> Result<(std::path::PathBuf, std::fs::File), Error> {
+        match std::fs::File::open(path) {
+            Ok(file) => Ok((path.to_path_buf(), file)),
+            Err(err) => match err.kind() {
+                std::io::ErrorKind::NotFound => {
+                    let fallback = path.with_extension("ndjson");
+                    match std::fs::File::open(&fallback) {
+                        Ok(file) => Ok((fallback, file)),
+                        Err(err) => with_path(&fallback, None, Err(err.into())),
+                    }
+                }
+                _ => with_path(path, None, Err(err.into())),
+            },
+        }
+    }
+
+    /// Construct an iterator that lazily deserializes each line of a newline-delimited JSON
+    /// (NDJSON) file at the given path, one record at a time.
+    ///
+    /// json_io will first try and open the file with the path exactly as given.
+    ///
+    /// If the file isn't found, it will set the extension to .ndjson and try again.
+    ///
+    /// Each item carries its own `Result`, so a single malformed record (reported as an
+    /// `Error::File` naming this file, wrapping an `Error::Line` naming its 1-based line number)
+    /// can be located or skipped without losing the rest of the file.
+    pub fn load_ndjson_iter<P, T>(path: P) -> Result<impl Iterator<Item = Result<T, Error>>, Error>
+        where P: AsRef<std::path::Path>,
+              T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let (resolved, file) = try!(open_ndjson(path));
+        Ok(NdjsonLines {
+            path: resolved,
+            lines: std::io::BufRead::lines(std::io::BufReader::new(file)),
+            line: 0,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    /// Construct a `Vec<T>` by eagerly deserializing every line of a newline-delimited JSON
+    /// (NDJSON) file at the given path.
+    ///
+    /// See `load_ndjson_iter` for details on the file lookup and the shape of per-line errors.
+    /// Unlike the iterator, this stops at the first record that fails and returns its error.
+    pub fn load_ndjson<P, T>(path: P) -> Result<Vec<T>, Error>
+        where P: AsRef<std::path::Path>,
+              T: serde::de::DeserializeOwned,
+    {
+        try!(load_ndjson_iter(path)).collect()
+    }
+
+    /// Save `items` to a newline-delimited JSON (NDJSON) file at the given path, one compact JSON
+    /// object per line.
+    ///
+    /// The file will be saved with the ".ndjson" extension whether or not it was given with the
+    /// Path. As with `save`, the write is atomic: every line is streamed into a sibling temporary
+    /// file, which is only renamed over the destination once all of `items` has been written
+    /// successfully, so a reader never observes a partial file.
+    pub fn save_ndjson<P, T, I>(path: P, items: I) -> Result<(), Error>
+        where P: AsRef<std::path::Path>,
+              T: serde::Serialize,
+              I: IntoIterator<Item = T>,
+    {
+        let path = path.as_ref();
+        let dest = path.with_extension("ndjson");
+        let result = atomic_write_with(&dest, |file| {
+            for item in items {
+                let line = try!(json_to_string(&item));
+                try!(std::io::Write::write_all(file, line.as_bytes()));
+                try!(std::io::Write::write_all(file, b"\n"));
+            }
+            Ok(())
+        });
+        with_path(&dest, None, result)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Create (freshly, discarding any leftovers from a previous run) a scratch directory
+        /// under the system temp dir, named after `name`, for the calling test to read and write
+        /// files in.
+        fn scratch_dir(name: &str) -> std::path::PathBuf {
+            let dir = std::env::temp_dir().join(format!("json_io_test_{}", name));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        }
+
+        #[test]
+        fn snippet_leaves_short_input_untouched() {
+            assert_eq!(snippet("hello"), "hello");
+        }
+
+        #[test]
+        fn snippet_truncates_long_input_on_a_char_boundary() {
+            // A multi-byte character repeated past the limit, to make sure truncation never
+            // splits a codepoint in half.
+            let long: String = "\u{e9}".repeat(SNIPPET_MAX_CHARS + 50);
+            let truncated = snippet(&long);
+            assert!(truncated.ends_with("..."));
+            assert_eq!(truncated.chars().count(), SNIPPET_MAX_CHARS + 3);
+        }
+
+        #[test]
+        fn format_from_path_falls_back_to_json_for_unknown_extensions() {
+            assert_eq!(Format::from_path("config.json"), Format::Json);
+            assert_eq!(Format::from_path("config.unknown"), Format::Json);
+            assert_eq!(Format::from_path("config"), Format::Json);
+        }
+
+        #[test]
+        fn save_as_and_load_as_round_trip() {
+            let dir = scratch_dir("save_as_and_load_as_round_trip");
+            let path = dir.join("config.json");
+
+            save_as(&path, &42, Format::Json).unwrap();
+            let value: i32 = load_as(&path, Format::Json).unwrap();
+            assert_eq!(value, 42);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn save_and_load_round_trip() {
+            let dir = scratch_dir("save_and_load_round_trip");
+            // `save` always targets the ".json" extension regardless of what's given here.
+            let path = dir.join("config");
+
+            save(&path, &vec![1, 2, 3]).unwrap();
+            let value: Vec<i32> = load(&path).unwrap();
+            assert_eq!(value, vec![1, 2, 3]);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Config {
+            servers: Vec<Server>,
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Server {
+            port: u16,
+        }
+
+        #[test]
+        fn load_reports_the_dotted_path_to_a_bad_nested_field() {
+            let dir = scratch_dir("load_reports_the_dotted_path_to_a_bad_nested_field");
+            let path = dir.join("config");
+
+            // `servers[2].port` is a string where a `u16` is expected.
+            let contents = r#"{"servers":[{"port":1},{"port":2},{"port":"not a number"}]}"#;
+            std::fs::write(path.with_extension("json"), contents).unwrap();
+
+            let result: Result<Config, Error> = load(&path);
+            match result {
+                Err(Error::File { source, .. }) => match *source {
+                    Error::DeserializePath { ref path, .. } => {
+                        assert_eq!(path, "servers[2].port");
+                    }
+                    ref other => panic!("expected Error::DeserializePath, got {:?}", other),
+                },
+                other => panic!("expected an Error::File, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn save_reports_the_dotted_path_to_a_bad_nested_field() {
+            let dir = scratch_dir("save_reports_the_dotted_path_to_a_bad_nested_field");
+            let path = dir.join("config");
+
+            #[derive(serde::Serialize)]
+            struct BadConfig {
+                servers: Vec<BadServer>,
+            }
+
+            struct BadServer;
+
+            impl serde::Serialize for BadServer {
+                fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+                    where S: serde::Serializer,
+                {
+                    Err(serde::ser::Error::custom("always fails to serialize"))
+                }
+            }
+
+            let config = BadConfig { servers: vec![BadServer] };
+
+            match save(&path, &config) {
+                Err(Error::File { source, .. }) => match *source {
+                    Error::SerializePath { ref path, .. } => {
+                        assert_eq!(path, "servers[0]");
+                    }
+                    ref other => panic!("expected Error::SerializePath, got {:?}", other),
+                },
+                other => panic!("expected an Error::File, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn save_ndjson_and_load_ndjson_round_trip() {
+            let dir = scratch_dir("save_ndjson_and_load_ndjson_round_trip");
+            // `save_ndjson` always targets the ".ndjson" extension regardless of what's given here.
+            let path = dir.join("records");
+
+            save_ndjson(&path, vec![1, 2, 3]).unwrap();
+            let value: Vec<i32> = load_ndjson(&path).unwrap();
+            assert_eq!(value, vec![1, 2, 3]);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn save_reports_the_path_it_actually_wrote() {
+            let dir = scratch_dir("save_reports_the_path_it_actually_wrote");
+
+            // `save` always writes to the ".json" extension; make the write itself fail (by
+            // pointing "path.json" at a directory) so the resulting error's path can be checked.
+            let path = dir.join("config");
+            std::fs::create_dir(path.with_extension("json")).unwrap();
+
+            match save(&path, &42) {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("json"));
+                }
+                other => panic!("expected an Error::File naming the .json path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        /// A type whose `Serialize` impl always fails, used to exercise `save`'s serialize-error
+        /// path without relying on some data shape `serde_json` happens to reject.
+        struct Unserializable;
+
+        impl serde::Serialize for Unserializable {
+            fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+                where S: serde::Serializer,
+            {
+                Err(serde::ser::Error::custom("always fails to serialize"))
+            }
+        }
+
+        #[test]
+        fn save_reports_the_json_path_on_a_serialize_failure() {
+            let dir = scratch_dir("save_reports_the_json_path_on_a_serialize_failure");
+
+            // The serialize failure happens before any write is attempted, so the resulting
+            // error's path must still name the ".json" destination, not the un-extended `path`.
+            let path = dir.join("config");
+
+            match save(&path, &Unserializable) {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("json"));
+                }
+                other => panic!("expected an Error::File naming the .json path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn save_failure_leaves_no_stray_tmp_file() {
+            let dir = scratch_dir("save_failure_leaves_no_stray_tmp_file");
+
+            let path = dir.join("config");
+            std::fs::create_dir(path.with_extension("json")).unwrap();
+
+            assert!(save(&path, &42).is_err());
+            let leftovers: Vec<_> = std::fs::read_dir(&dir).unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .filter(|name| name.to_string_lossy().contains(".tmp"))
+                .collect();
+            assert!(leftovers.is_empty(), "expected no .tmp files, found {:?}", leftovers);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn concurrent_saves_to_the_same_path_never_corrupt_each_other() {
+            let dir = scratch_dir("concurrent_saves_to_the_same_path");
+            let path = dir.join("config");
+
+            let path_a = path.clone();
+            let path_b = path.clone();
+            let a = std::thread::spawn(move || save(&path_a, &1));
+            let b = std::thread::spawn(move || save(&path_b, &2));
+            a.join().unwrap().unwrap();
+            b.join().unwrap().unwrap();
+
+            // Whichever write won the race, the result must be one of the two complete values,
+            // never a mangled mix of both (which would happen if both threads shared one tmp
+            // file).
+            let value: i32 = load(&path).unwrap();
+            assert!(value == 1 || value == 2);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn load_reports_the_path_it_actually_opened() {
+            let dir = scratch_dir("load_reports_the_path_it_actually_opened");
+
+            // Only "config.json" exists; `load` should fall back to it and, on failure, name
+            // *that* path rather than the original "config".
+            let path = dir.join("config");
+            std::fs::write(path.with_extension("json"), "not json").unwrap();
+
+            let result: Result<i32, Error> = load(&path);
+            match result {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("json"));
+                }
+                other => panic!("expected an Error::File naming the .json path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn load_reports_the_json_fallback_path_when_neither_file_exists() {
+            let dir = scratch_dir("load_reports_the_json_fallback_path_when_neither_file_exists");
+
+            // Neither "config" nor "config.json" exists; `load` still probes the fallback, so
+            // the error should name it, not the original "config".
+            let path = dir.join("config");
+
+            let result: Result<i32, Error> = load(&path);
+            match result {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("json"));
+                }
+                other => panic!("expected an Error::File naming the .json path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn load_ndjson_iter_numbers_lines_from_one_and_names_the_file() {
+            let dir = scratch_dir("load_ndjson_iter_numbers_lines_from_one");
+            let path = dir.join("records.ndjson");
+            std::fs::write(&path, "1\n2\nnot json\n4\n").unwrap();
+
+            let results: Vec<Result<i32, Error>> = load_ndjson_iter(&path).unwrap().collect();
+            assert_eq!(results.len(), 4);
+            assert_eq!(results[0].as_ref().unwrap(), &1);
+            assert_eq!(results[1].as_ref().unwrap(), &2);
+            match results[2] {
+                Err(Error::File { ref path, ref source, .. }) => {
+                    assert_eq!(path, &dir.join("records.ndjson"));
+                    match **source {
+                        Error::Line { line, .. } => assert_eq!(line, 3),
+                        ref other => panic!("expected Error::Line, got {:?}", other),
+                    }
+                }
+                ref other => panic!("expected an Error::File, got {:?}", other),
+            }
+            assert_eq!(results[3].as_ref().unwrap(), &4);
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn load_ndjson_reports_the_path_it_actually_opened() {
+            let dir = scratch_dir("load_ndjson_reports_the_path_it_actually_opened");
+
+            // Only "records.ndjson" exists; `load_ndjson` should fall back to it and, on
+            // failure, name *that* path rather than the original "records".
+            let path = dir.join("records");
+            std::fs::write(path.with_extension("ndjson"), "1\nnot json\n").unwrap();
+
+            let result: Result<Vec<i32>, Error> = load_ndjson(&path);
+            match result {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("ndjson"));
+                }
+                other => panic!("expected an Error::File naming the .ndjson path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+
+        #[test]
+        fn load_ndjson_reports_the_ndjson_fallback_path_when_neither_file_exists() {
+            let dir = scratch_dir(
+                "load_ndjson_reports_the_ndjson_fallback_path_when_neither_file_exists");
+
+            // Neither "records" nor "records.ndjson" exists; `load_ndjson` still probes the
+            // fallback, so the error should name it, not the original "records".
+            let path = dir.join("records");
+
+            let result: Result<Vec<i32>, Error> = load_ndjson(&path);
+            match result {
+                Err(Error::File { path: ref err_path, .. }) => {
+                    assert_eq!(err_path, &path.with_extension("ndjson"));
+                }
+                other => panic!("expected an Error::File naming the .ndjson path, got {:?}", other),
+            }
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
     }
 }
 